@@ -1,13 +1,18 @@
-use std::{env, path::PathBuf};
+use std::{collections::HashMap, env, path::PathBuf, str::FromStr};
 
 use openrgb2::Color as OrgbColor;
 use serde::{Deserialize, Serialize};
 
+use crate::input::Backend;
+
 pub fn get_config_dir() -> PathBuf {
     let home = env::home_dir().unwrap();
     home.join(PathBuf::from_iter([".config", "openrgb-fade"]))
 }
 
+/// Free-form per-effect settings, keyed by whatever the effect implementation expects.
+pub type Options = HashMap<String, serde_json::Value>;
+
 #[derive(Deserialize, Serialize, Clone, Copy, Debug)]
 pub struct Color {
     pub r: u8,
@@ -15,7 +20,7 @@ pub struct Color {
     pub b: u8,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct SDKServerInfo {
     pub address: Option<String>,
     pub port: Option<u16>,
@@ -27,11 +32,127 @@ impl From<Color> for OrgbColor {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Named groups of evdev key codes (see `linux/input-event-codes.h`), so `key_colors` can
+/// target a section of the keyboard without listing every code by hand. Only meaningful
+/// for devices read through the evdev backend, since HID report codes aren't standardized.
+fn named_group(name: &str) -> Option<&'static [u16]> {
+    const WASD: [u16; 4] = [17, 30, 31, 32];
+    const MODIFIERS: [u16; 8] = [29, 42, 54, 56, 97, 100, 125, 126];
+    const NUMBER_ROW: [u16; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+    match name {
+        "wasd" => Some(&WASD),
+        "modifiers" => Some(&MODIFIERS),
+        "number_row" => Some(&NUMBER_ROW),
+        _ => None,
+    }
+}
+
+/// Either a single color, or a gradient sampled by a key's normalized position across
+/// the keyboard.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ColorSpec {
+    Solid(Color),
+    Gradient(Vec<Color>),
+}
+
+impl ColorSpec {
+    /// Resolve to a concrete color. `t` is the key's position across the keyboard,
+    /// normalized to `[0, 1]`, used to pick a stop along a gradient.
+    fn resolve(&self, t: f32) -> OrgbColor {
+        match self {
+            Self::Solid(color) => OrgbColor::from(*color),
+            Self::Gradient(stops) => match stops.as_slice() {
+                [] => OrgbColor::new(0, 0, 0),
+                [only] => OrgbColor::from(*only),
+                stops => {
+                    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+                    let idx = scaled.floor() as usize;
+                    let frac = scaled - idx as f32;
+
+                    let a = stops[idx];
+                    let b = stops[(idx + 1).min(stops.len() - 1)];
+
+                    OrgbColor::new(
+                        lerp_channel(a.r, b.r, frac),
+                        lerp_channel(a.g, b.g, frac),
+                        lerp_channel(a.b, b.b, frac),
+                    )
+                }
+            },
+        }
+    }
+}
+
+fn lerp_channel(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t).round() as u8
+}
+
+/// A fadeout curve applied to the normalized progress `t` (`1.0` at the moment a key is
+/// pressed, `0.0` once the LED should be fully off).
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseOutQuad,
+    EaseOutCubic,
+    Exponential,
+}
+
+impl Easing {
+    /// Apply this curve to `t`, returning the brightness factor to scale the base color by.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseOutQuad => 1.0 - (1.0 - t).powi(2),
+            Self::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Self::Exponential => {
+                if t == 0.0 {
+                    0.0
+                } else {
+                    2f32.powf(10.0 * (t - 1.0))
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Self::Linear),
+            "ease-out-quad" => Ok(Self::EaseOutQuad),
+            "ease-out-cubic" => Ok(Self::EaseOutCubic),
+            "exponential" => Ok(Self::Exponential),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     color: Color,
     fps: Option<usize>,
     fadeout_time_ms: Option<usize>,
+    easing: Option<String>,
+    effect: Option<String>,
+    #[serde(default)]
+    options: Options,
+    /// Per-key colors, keyed by either a literal key code (e.g. `"30"`) or a named group
+    /// (e.g. `"wasd"`).
+    #[serde(default)]
+    key_colors: HashMap<String, ColorSpec>,
+    /// Per-controller override pointing a controller's `location()` (always an OpenRGB
+    /// `"HID: /dev/hidrawN"` string) at the input node to actually read key presses from —
+    /// e.g. the companion `/dev/input/eventN` node for a keyboard whose HID reports this
+    /// crate's HID backend can't decode.
+    #[serde(default)]
+    input_devices: HashMap<String, String>,
     server: Option<SDKServerInfo>,
 }
 
@@ -46,11 +167,60 @@ impl Config {
         self.fps.unwrap_or(60)
     }
 
-    // TODO: implement
     pub fn fadeout_time_ms(&self) -> usize {
         self.fadeout_time_ms.unwrap_or(1000)
     }
 
+    /// The easing curve to fade brightness along. Defaults to `linear`, and falls back to
+    /// it if `easing` names a curve we don't recognize.
+    pub fn easing(&self) -> Easing {
+        self.easing
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Name of the effect to run, as looked up in the effect registry. Defaults to `"fade"`.
+    pub fn effect(&self) -> &str {
+        self.effect.as_deref().unwrap_or("fade")
+    }
+
+    /// Free-form options passed through to the selected effect.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Resolve the color a key should fade in with: an exact `key_colors` entry for its
+    /// code wins, then the first named group containing it. Returns `None` if nothing
+    /// matches, leaving the caller to fall back to its own default.
+    /// `t` is the key's position across the keyboard, normalized to `[0, 1]`, used to
+    /// sample gradient entries. Named groups are keyed on evdev keycodes, so they're only
+    /// consulted for `Backend::Evdev` events — a raw HID report byte happening to equal one
+    /// isn't meaningful.
+    pub fn key_color_override(&self, backend: Backend, code: u16, t: f32) -> Option<OrgbColor> {
+        if let Some(spec) = self.key_colors.get(&code.to_string()) {
+            return Some(spec.resolve(t));
+        }
+
+        if backend != Backend::Evdev {
+            return None;
+        }
+
+        self.key_colors
+            .iter()
+            .find(|(name, _)| named_group(name).is_some_and(|codes| codes.contains(&code)))
+            .map(|(_, spec)| spec.resolve(t))
+    }
+
+    /// The path to actually open for reading key presses from this controller: an explicit
+    /// `input_devices` override if one is configured, otherwise `controller_location` as-is.
+    pub fn input_path<'a>(&'a self, controller_location: &'a str) -> &'a str {
+        self.input_devices
+            .get(controller_location)
+            .map(String::as_str)
+            .unwrap_or(controller_location)
+    }
+
     pub fn load_from_first() -> Option<Self> {
         let path = get_config_dir().join(PathBuf::from("config.jsonc"));
 
@@ -73,3 +243,103 @@ impl Config {
         serde_jsonc::from_str(DEFAULT_CONFIG).ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_is_identity() {
+        assert_eq!(Easing::Linear.apply(0.0), 0.0);
+        assert_eq!(Easing::Linear.apply(0.5), 0.5);
+        assert_eq!(Easing::Linear.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn ease_out_curves_start_at_zero_and_end_at_one() {
+        for easing in [Easing::EaseOutQuad, Easing::EaseOutCubic] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            // Ease-out curves front-load brightness: past the midpoint, ahead of linear.
+            assert!(easing.apply(0.5) > 0.5);
+        }
+    }
+
+    #[test]
+    fn exponential_is_zero_only_at_zero() {
+        assert_eq!(Easing::Exponential.apply(0.0), 0.0);
+        assert_eq!(Easing::Exponential.apply(1.0), 1.0);
+        assert!(Easing::Exponential.apply(0.5) > 0.0);
+    }
+
+    #[test]
+    fn apply_clamps_out_of_range_input() {
+        assert_eq!(Easing::Linear.apply(-1.0), 0.0);
+        assert_eq!(Easing::Linear.apply(2.0), 1.0);
+    }
+
+    #[test]
+    fn from_str_rejects_unknown_names() {
+        assert!("linear".parse::<Easing>().is_ok());
+        assert!("bogus".parse::<Easing>().is_err());
+    }
+
+    fn rgb(color: OrgbColor) -> (u8, u8, u8) {
+        (color.r, color.g, color.b)
+    }
+
+    #[test]
+    fn solid_color_spec_ignores_t() {
+        let spec = ColorSpec::Solid(Color { r: 1, g: 2, b: 3 });
+        assert_eq!(rgb(spec.resolve(0.0)), (1, 2, 3));
+        assert_eq!(rgb(spec.resolve(1.0)), (1, 2, 3));
+    }
+
+    #[test]
+    fn empty_gradient_resolves_to_black() {
+        let spec = ColorSpec::Gradient(vec![]);
+        assert_eq!(rgb(spec.resolve(0.5)), (0, 0, 0));
+    }
+
+    #[test]
+    fn gradient_resolves_exactly_at_stops() {
+        let spec = ColorSpec::Gradient(vec![
+            Color { r: 0, g: 0, b: 0 },
+            Color {
+                r: 100,
+                g: 100,
+                b: 100,
+            },
+            Color {
+                r: 200,
+                g: 200,
+                b: 200,
+            },
+        ]);
+
+        assert_eq!(rgb(spec.resolve(0.0)), (0, 0, 0));
+        assert_eq!(rgb(spec.resolve(0.5)), (100, 100, 100));
+        assert_eq!(rgb(spec.resolve(1.0)), (200, 200, 200));
+    }
+
+    #[test]
+    fn gradient_interpolates_between_stops() {
+        let spec = ColorSpec::Gradient(vec![
+            Color { r: 0, g: 0, b: 0 },
+            Color { r: 200, g: 0, b: 0 },
+        ]);
+
+        assert_eq!(rgb(spec.resolve(0.25)), (50, 0, 0));
+    }
+
+    #[test]
+    fn gradient_clamps_out_of_range_t() {
+        let spec = ColorSpec::Gradient(vec![
+            Color { r: 0, g: 0, b: 0 },
+            Color { r: 200, g: 0, b: 0 },
+        ]);
+
+        assert_eq!(rgb(spec.resolve(-1.0)), (0, 0, 0));
+        assert_eq!(rgb(spec.resolve(2.0)), (200, 0, 0));
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use inotify::{EventMask, Inotify, WatchMask};
+
+/// A hidraw or event node appearing or disappearing under `/dev` / `/dev/input`.
+#[derive(Debug)]
+pub enum DeviceChange {
+    Added(String),
+    Removed(String),
+}
+
+/// Watch `/dev` and `/dev/input` for hidraw/event nodes appearing or disappearing. Runs on
+/// its own thread since inotify's blocking read doesn't fit the rest of the async pipeline.
+///
+/// Never brings the daemon down: if inotify can't be set up (no permission to watch
+/// `/dev` in a sandboxed environment, `max_user_instances` exhausted, ...) or the watch
+/// drops partway through, this logs and retries with backoff instead of panicking. The
+/// caller falls back to its periodic poll cadence for as long as no events arrive.
+pub fn watch() -> Receiver<DeviceChange> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut retry_delay = Duration::from_secs(1);
+
+        loop {
+            match try_watch(&tx) {
+                Ok(()) => return,
+                Err(err) => {
+                    println!(
+                        "Hotplug watcher failed ({err}); falling back to polling, retrying in {retry_delay:?}."
+                    );
+                    thread::sleep(retry_delay);
+                    retry_delay = (retry_delay * 2).min(Duration::from_secs(30));
+                }
+            }
+        }
+    });
+
+    rx
+}
+
+/// Runs the watch loop until the receiver is dropped (`Ok(())`, stop for good) or inotify
+/// itself fails (`Err`, caller should back off and try again).
+fn try_watch(tx: &Sender<DeviceChange>) -> std::io::Result<()> {
+    let mut inotify = Inotify::init()?;
+
+    inotify
+        .watches()
+        .add("/dev", WatchMask::CREATE | WatchMask::DELETE)?;
+    inotify
+        .watches()
+        .add("/dev/input", WatchMask::CREATE | WatchMask::DELETE)?;
+
+    let mut buffer = [0; 4096];
+
+    loop {
+        let events = inotify.read_events_blocking(&mut buffer)?;
+
+        for event in events {
+            let Some(name) = event.name.and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let path = if name.starts_with("hidraw") {
+                format!("/dev/{name}")
+            } else if name.starts_with("event") {
+                format!("/dev/input/{name}")
+            } else {
+                continue;
+            };
+
+            let change = if event.mask.contains(EventMask::CREATE) {
+                // Give udev a moment to finish setting permissions before we open it.
+                thread::sleep(Duration::from_millis(200));
+                DeviceChange::Added(path)
+            } else if event.mask.contains(EventMask::DELETE) {
+                DeviceChange::Removed(path)
+            } else {
+                continue;
+            };
+
+            if tx.send(change).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Whether an OpenRGB controller `location` (e.g. `"HID: /dev/hidraw3"`) refers to `node_path`.
+pub fn location_matches(location: &str, node_path: &str) -> bool {
+    location.trim_start_matches("HID: ") == node_path
+}
@@ -0,0 +1,18 @@
+use openrgb2::Controller;
+
+use crate::{LedFunction, config::Options, fade::FadeLeds, static_color::StaticColor};
+
+/// Build the [`LedFunction`] registered under `name`, configured with `opts`.
+///
+/// Unrecognized names fall back to `"fade"` so a typo in the config doesn't leave a
+/// device without any effect running.
+pub fn build_effect(name: &str, opts: &Options, controller: &Controller) -> Box<dyn LedFunction> {
+    match name {
+        "fade" => Box::new(FadeLeds::new(controller, opts)),
+        "static" => Box::new(StaticColor::new(controller, opts)),
+        other => {
+            println!("Unknown effect \"{other}\", falling back to \"fade\".");
+            Box::new(FadeLeds::new(controller, opts))
+        }
+    }
+}
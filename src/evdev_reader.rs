@@ -0,0 +1,31 @@
+use std::io;
+
+use crate::input::{Backend, KeyEvent};
+
+/// Reads standardized `EV_KEY` events from a `/dev/input/eventX` node. Unlike `HidReader`,
+/// keycodes here are already normalized by the kernel, so this works on any keyboard
+/// regardless of how its HID reports are laid out.
+pub struct EvdevReader {
+    device: evdev::Device,
+}
+
+impl EvdevReader {
+    pub fn new_from_path(path: &str) -> Option<Self> {
+        let device = evdev::Device::open(path).ok()?;
+        Some(Self { device })
+    }
+
+    pub fn read_blocking(&mut self) -> io::Result<KeyEvent> {
+        loop {
+            for event in self.device.fetch_events()? {
+                if event.event_type() == evdev::EventType::KEY {
+                    return Ok(KeyEvent::new(
+                        Backend::Evdev,
+                        event.code(),
+                        event.value() != 0,
+                    ));
+                }
+            }
+        }
+    }
+}
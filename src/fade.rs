@@ -1,89 +1,136 @@
 use openrgb2::{Color, Controller, OpenRgbResult};
 
-use crate::LedFunction;
+use crate::{
+    LedFunction,
+    config::{Color as ConfigColor, Easing, Options},
+};
 
 #[derive(Default, Clone, Copy, Debug)]
 pub enum FadeState {
     #[default]
     Off,
-    On(Brightness),
+    On(Progress, Color),
 }
 
 impl FadeState {
-    pub fn update(&mut self) {
-        if let Self::On(brightness) = self
-            && brightness.tick().is_none()
+    /// Advance the fade by one frame, stepping progress down by `step` (`frame_time_ms /
+    /// fadeout_time_ms`). Transitions to `Off` once progress bottoms out.
+    pub fn update(&mut self, step: f32) {
+        if let Self::On(progress, _) = self
+            && progress.tick(step).is_none()
         {
             *self = FadeState::Off;
         }
     }
 
-    pub fn get_brightness(&self) -> u8 {
+    pub fn brightness_factor(&self, easing: Easing) -> f32 {
         match self {
-            Self::On(brightness) => brightness.0,
-            Self::Off => 0,
+            Self::On(progress, _) => easing.apply(progress.0),
+            Self::Off => 0.0,
+        }
+    }
+
+    /// The base color this key is fading from, if it's lit.
+    pub fn color(&self) -> Option<Color> {
+        match self {
+            Self::On(_, color) => Some(*color),
+            Self::Off => None,
         }
     }
 }
 
+/// Normalized fade progress: `1.0` the instant a key lights up, `0.0` once it's fully off.
 #[derive(Clone, Copy, Debug)]
-pub struct Brightness(u8);
+pub struct Progress(f32);
 
-impl Brightness {
-    pub const MAX: Brightness = Brightness(255);
+impl Progress {
+    pub const START: Progress = Progress(1.0);
 
-    pub fn tick(&mut self) -> Option<()> {
-        if self.0 == 0 {
+    /// Step progress down by `step`, returning `None` once it reaches (or instantly hits,
+    /// for `step >= 1.0`) zero.
+    pub fn tick(&mut self, step: f32) -> Option<()> {
+        if self.0 <= 0.0 {
             None
         } else {
-            self.0 -= 1;
-            Some(())
+            self.0 = (self.0 - step).max(0.0);
+            if self.0 <= 0.0 { None } else { Some(()) }
         }
     }
 }
 
 pub struct FadeLeds {
     state: Vec<FadeState>,
+    /// `options.color` / `options.easing` overrides for this device, taking priority over
+    /// the global config but under explicit per-key colors.
+    color_override: Option<Color>,
+    easing_override: Option<Easing>,
+}
+
+/// Pull a per-device override for `key` out of this effect's `options` table, ignoring it
+/// (rather than failing construction) if it's present but malformed.
+fn parse_option<T: serde::de::DeserializeOwned>(opts: &Options, key: &str) -> Option<T> {
+    opts.get(key)
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
 }
 
 impl LedFunction for FadeLeds {
-    fn new(controller: &Controller) -> Self {
+    fn new(controller: &Controller, opts: &Options) -> Self {
+        let color_override = parse_option::<ConfigColor>(opts, "color").map(Color::from);
+        let easing_override = parse_option::<String>(opts, "easing").and_then(|s| s.parse().ok());
+
         Self {
             state: vec![FadeState::Off; controller.num_leds()],
+            color_override,
+            easing_override,
         }
     }
 
     fn update(
         &mut self,
         config: &crate::config::Config,
-        events: &[crate::hid::KeyEvent],
+        events: &[crate::input::KeyEvent],
         key_map: &crate::key_mappings::KeyMapping,
         controller: &Controller,
     ) -> OpenRgbResult<()> {
+        let num_leds = self.state.len();
+
         for event in events.iter() {
             if event.is_down()
-                && let Some(led) = key_map.get_led(event.key_bytes())
+                && let Some(led) = key_map.get_led(event)
             {
-                self.state[led] = FadeState::On(Brightness::MAX)
+                let t = if num_leds > 1 {
+                    led as f32 / (num_leds - 1) as f32
+                } else {
+                    0.0
+                };
+
+                let color = config
+                    .key_color_override(event.backend(), event.key_bytes(), t)
+                    .or(self.color_override)
+                    .unwrap_or_else(|| config.color());
+
+                self.state[led] = FadeState::On(Progress::START, color);
             }
         }
 
-        let color = config.color();
+        let frame_time_ms = 1000.0 / config.fps() as f32;
+        let fadeout_time_ms = config.fadeout_time_ms();
+        let step = if fadeout_time_ms == 0 {
+            1.0
+        } else {
+            frame_time_ms / fadeout_time_ms as f32
+        };
+        let easing = self.easing_override.unwrap_or_else(|| config.easing());
+
         let mut cmd = controller.cmd();
 
         for led in controller.led_iter() {
             let state = self.state.get_mut(led.id()).unwrap();
-            state.update();
-
-            let brightness = state.get_brightness();
-
-            let new_color = if brightness == 0 {
-                Color::new(0, 0, 0)
-            } else {
-                color / (255 - brightness)
-            };
+            state.update(step);
 
-            cmd.set_led(led.id(), new_color)?;
+            let factor = state.brightness_factor(easing);
+            let base_color = state.color().unwrap_or(Color::new(0, 0, 0));
+            cmd.set_led(led.id(), scale_color(base_color, factor))?;
         }
 
         futures_lite::future::block_on(cmd.execute())?;
@@ -91,3 +138,11 @@ impl LedFunction for FadeLeds {
         Ok(())
     }
 }
+
+/// Scale `color` per-channel by `factor` (clamped to `[0, 1]`), rounding to the nearest `u8`.
+fn scale_color(color: Color, factor: f32) -> Color {
+    let factor = factor.clamp(0.0, 1.0);
+    let scale = |channel: u8| ((channel as f32) * factor).round() as u8;
+
+    Color::new(scale(color.r), scale(color.g), scale(color.b))
+}
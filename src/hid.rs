@@ -2,6 +2,8 @@ use std::{ffi::CString, str::FromStr};
 
 use hidapi::{HidDevice, HidError};
 
+use crate::input::{Backend, KeyEvent};
+
 // I barely know what I'm doing! HID reports can probably be more complicated than
 // this thing can cover, but for my specific keyboard (Vulkan TKL), this works well enough. :)
 pub struct HidReader<const B: usize = 1024> {
@@ -28,18 +30,10 @@ impl<const B: usize> HidReader<B> {
     pub fn read_blocking(&mut self) -> Result<KeyEvent, HidError> {
         let size = self.device.read_timeout(&mut self.buffer, -1)?;
         let slice = &self.buffer[0..size];
-        Ok(KeyEvent(Vec::from(slice)))
-    }
-}
 
-pub struct KeyEvent(Vec<u8>);
-
-impl KeyEvent {
-    pub fn is_down(&self) -> bool {
-        self.0.len() >= 5 && self.0[4] > 0
-    }
+        let code = u16::from_ne_bytes([slice[2], slice[3]]);
+        let down = slice.len() >= 5 && slice[4] > 0;
 
-    pub fn key_bytes(&self) -> u16 {
-        u16::from_ne_bytes([self.0[2], self.0[3]])
+        Ok(KeyEvent::new(Backend::Hid, code, down))
     }
 }
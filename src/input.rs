@@ -0,0 +1,71 @@
+use crate::{evdev_reader::EvdevReader, hid::HidReader};
+
+/// Which physical interface produced a [`KeyEvent`]'s code.
+///
+/// HID report bytes and evdev keycodes are both small integers and can collide, so every
+/// code is tagged with the backend that produced it to keep the two spaces apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Hid,
+    Evdev,
+}
+
+pub struct KeyEvent {
+    backend: Backend,
+    code: u16,
+    down: bool,
+}
+
+impl KeyEvent {
+    pub(crate) fn new(backend: Backend, code: u16, down: bool) -> Self {
+        Self {
+            backend,
+            code,
+            down,
+        }
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.down
+    }
+
+    pub fn key_bytes(&self) -> u16 {
+        self.code
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Hid(hidapi::HidError),
+    Evdev(std::io::Error),
+}
+
+/// Either of the interfaces we can read key presses from: `HidReader`'s vendor-specific
+/// report parsing, or `EvdevReader`'s standardized kernel keycodes.
+pub enum InputSource<const B: usize = 256> {
+    Hid(HidReader<B>),
+    Evdev(EvdevReader),
+}
+
+impl<const B: usize> InputSource<B> {
+    /// Open `path` on whichever backend understands it: a `/dev/input/eventX` node goes to
+    /// evdev, anything else (OpenRGB's `HID: ...`-prefixed hidraw locations) goes to HID.
+    pub fn new_from_path(path: &str) -> Option<Self> {
+        if path.contains("/event") {
+            EvdevReader::new_from_path(path).map(Self::Evdev)
+        } else {
+            HidReader::new_from_path(path).map(Self::Hid)
+        }
+    }
+
+    pub fn read_blocking(&mut self) -> Result<KeyEvent, InputError> {
+        match self {
+            Self::Hid(hid) => hid.read_blocking().map_err(InputError::Hid),
+            Self::Evdev(evdev) => evdev.read_blocking().map_err(InputError::Evdev),
+        }
+    }
+}
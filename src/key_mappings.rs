@@ -1,36 +1,118 @@
+use std::collections::{HashMap, hash_map::Entry};
+
+use crate::input::{Backend, KeyEvent};
+
 #[derive(Clone, Debug)]
-pub struct KeyMapping(Vec<u16>);
+pub struct KeyMapping {
+    keys: Vec<(Backend, u16)>,
+    lookup: HashMap<(Backend, u16), usize>,
+}
 
 impl KeyMapping {
     pub fn parse_from_file(file_contents: String) -> Option<Self> {
-        file_contents
+        let mut is_legacy_format = false;
+
+        let keys = file_contents
             .split("\n")
-            .map(|line| line.parse())
-            .collect::<Result<Vec<u16>, _>>()
-            .ok()
-            .map(Self::from)
+            .map(|line| match line.split_once(':') {
+                Some((backend, code)) => {
+                    let backend = match backend {
+                        "hid" => Backend::Hid,
+                        "evdev" => Backend::Evdev,
+                        _ => return None,
+                    };
+
+                    code.parse().ok().map(|code| (backend, code))
+                }
+                // Keymaps from before the evdev backend were a bare code per line, always HID.
+                None => {
+                    is_legacy_format = true;
+                    line.parse().ok().map(|code| (Backend::Hid, code))
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if is_legacy_format {
+            println!(
+                "This keymap file uses the pre-evdev format; treating every key as HID. Re-run setup to pick up evdev support."
+            );
+        }
+
+        Some(Self::from(keys))
     }
 
     pub fn as_file_string(&self) -> String {
-        self.0
+        self.keys
             .iter()
-            .copied()
-            .map(|key| format!("{}", key))
+            .map(|(backend, code)| {
+                let backend = match backend {
+                    Backend::Hid => "hid",
+                    Backend::Evdev => "evdev",
+                };
+
+                format!("{backend}:{code}")
+            })
             .collect::<Vec<_>>()
             .join("\n")
     }
 
     /// Get the LED index corresponding to the input key.
-    pub fn get_led(&self, key: u16) -> Option<usize> {
-        self.0
-            .iter()
-            .enumerate()
-            .find_map(|(idx, other)| if key == *other { Some(idx) } else { None })
+    pub fn get_led(&self, event: &KeyEvent) -> Option<usize> {
+        self.lookup
+            .get(&(event.backend(), event.key_bytes()))
+            .copied()
     }
 }
 
-impl From<Vec<u16>> for KeyMapping {
-    fn from(value: Vec<u16>) -> Self {
-        Self(value)
+impl From<Vec<(Backend, u16)>> for KeyMapping {
+    /// Keeps the *first* LED index for a duplicated `(backend, code)` entry, matching the
+    /// old linear scan's behavior, and warns about it instead of silently repointing which
+    /// LED a hand-edited keymap's repeated line resolves to.
+    fn from(value: Vec<(Backend, u16)>) -> Self {
+        let mut lookup = HashMap::with_capacity(value.len());
+
+        for (idx, key) in value.iter().copied().enumerate() {
+            match lookup.entry(key) {
+                Entry::Occupied(_) => {
+                    println!("Duplicate keymap entry for {key:?}; keeping the first occurrence.");
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(idx);
+                }
+            }
+        }
+
+        Self {
+            keys: value,
+            lookup,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_by_backend_and_code() {
+        let map = KeyMapping::from(vec![(Backend::Hid, 30), (Backend::Evdev, 30)]);
+
+        assert_eq!(map.get_led(&KeyEvent::new(Backend::Hid, 30, true)), Some(0));
+        assert_eq!(
+            map.get_led(&KeyEvent::new(Backend::Evdev, 30, true)),
+            Some(1)
+        );
+        assert_eq!(map.get_led(&KeyEvent::new(Backend::Hid, 31, true)), None);
+    }
+
+    #[test]
+    fn duplicate_entry_keeps_first_occurrence() {
+        let map = KeyMapping::from(vec![
+            (Backend::Hid, 30),
+            (Backend::Hid, 31),
+            (Backend::Hid, 30),
+        ]);
+
+        assert_eq!(map.get_led(&KeyEvent::new(Backend::Hid, 30, true)), Some(0));
     }
 }
@@ -3,7 +3,7 @@ use std::{
     path::PathBuf,
     pin::Pin,
     str::FromStr,
-    sync::mpsc::{self, Receiver, TryRecvError},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
     task::{Context, Poll},
     time::Duration,
 };
@@ -13,16 +13,22 @@ use openrgb2::{Color, Controller, DeviceType, OpenRgbClient, OpenRgbError, OpenR
 use smol::Timer;
 
 use crate::{
-    config::{Config, get_config_dir},
-    fade::FadeLeds,
-    hid::{HidReader, KeyEvent},
+    config::{Config, Options, get_config_dir},
+    device_watch::DeviceChange,
+    effects::build_effect,
+    input::{InputSource, KeyEvent},
     key_mappings::KeyMapping,
 };
 
 mod config;
+mod device_watch;
+mod effects;
+mod evdev_reader;
 mod fade;
 mod hid;
+mod input;
 mod key_mappings;
+mod static_color;
 
 static BASE_COLOR: Color = Color::new(255, 100, 255);
 
@@ -50,8 +56,9 @@ fn get_keymap_filepath(controller: &Controller) -> PathBuf {
     config.join(PathBuf::from_str(&format!("{}-{}.keymap", vendor, name)).unwrap())
 }
 
-async fn setup_device(device: &Controller) -> OpenRgbResult<KeyMapping> {
-    let mut hid = HidReader::<512>::new_from_path(device.location()).unwrap();
+async fn setup_device(device: &Controller, config: &Config) -> OpenRgbResult<KeyMapping> {
+    let mut input =
+        InputSource::<512>::new_from_path(config.input_path(device.location())).unwrap();
 
     println!("Press the keys as they light up.");
 
@@ -59,8 +66,8 @@ async fn setup_device(device: &Controller) -> OpenRgbResult<KeyMapping> {
 
     let mut get_next_unique_event = move || {
         loop {
-            let event = hid.read_blocking().unwrap();
-            let key = event.key_bytes();
+            let event = input.read_blocking().unwrap();
+            let key = (event.backend(), event.key_bytes());
 
             if !seen.contains(&key) {
                 seen.insert(key);
@@ -77,7 +84,7 @@ async fn setup_device(device: &Controller) -> OpenRgbResult<KeyMapping> {
 
         let event = get_next_unique_event();
 
-        keys.push(event.key_bytes());
+        keys.push((event.backend(), event.key_bytes()));
     }
 
     Ok(KeyMapping::from(keys))
@@ -97,7 +104,7 @@ async fn wait_for_server() -> OpenRgbClient {
 }
 
 pub trait LedFunction {
-    fn new(controller: &Controller) -> Self
+    fn new(controller: &Controller, opts: &Options) -> Self
     where
         Self: Sized;
 
@@ -139,7 +146,7 @@ async fn main() -> OpenRgbResult<()> {
                     std::process::exit(1);
                 }
 
-                let keymap = setup_device(&device).await?;
+                let keymap = setup_device(&device, &config).await?;
                 println!("Finished setting up {} at {}.", device.name(), "");
 
                 std::fs::write(out_file, keymap.as_file_string()).unwrap();
@@ -155,77 +162,86 @@ async fn main() -> OpenRgbResult<()> {
     let sleep_time = 1000 / config.fps() as u64;
     println!("Frame time: {sleep_time}ms");
 
-    let try_setup_thread = |controller: Controller| -> Option<(String, Receiver<()>)> {
-        let keymap_file = std::fs::read_to_string(get_keymap_filepath(&controller)).ok()?;
-        let keymap = KeyMapping::parse_from_file(keymap_file)?;
+    let try_setup_thread =
+        |controller: Controller| -> Option<(String, Receiver<()>, mpsc::Sender<()>)> {
+            let keymap_file = std::fs::read_to_string(get_keymap_filepath(&controller)).ok()?;
+            let keymap = KeyMapping::parse_from_file(keymap_file)?;
 
-        let mut hid = HidReader::<256>::new_from_path(controller.location())?;
+            let mut input =
+                InputSource::<256>::new_from_path(config.input_path(controller.location()))?;
 
-        let (tx, hid_event_reader) = mpsc::channel();
+            let (tx, hid_event_reader) = mpsc::channel();
 
-        std::thread::spawn(move || {
-            while let Ok(event) = hid.read_blocking() {
-                if tx.send(event).is_err() {
-                    return;
+            std::thread::spawn(move || {
+                while let Ok(event) = input.read_blocking() {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
                 }
-            }
-        });
+            });
+
+            let (tx, thread_exited) = mpsc::channel();
+            let (stop_tx, stop_rx) = mpsc::channel();
+
+            let location = controller.location().to_string();
+            let config = config.clone();
+
+            println!(
+                "Spawning thread for {} (at {})",
+                controller.name(),
+                controller.location()
+            );
+
+            std::thread::spawn(move || {
+                if smol::block_on(async {
+                    controller.init().await?;
+                    controller.turn_off_leds().await?;
+                    Ok::<(), OpenRgbError>(())
+                })
+                .is_err()
+                {
+                    tx.send(()).unwrap();
+                    return;
+                };
 
-        let (tx, thread_exited) = mpsc::channel();
+                let mut func = build_effect(config.effect(), config.options(), &controller);
+                'outer: loop {
+                    std::thread::sleep(Duration::from_millis(sleep_time));
 
-        let location = controller.location().to_string();
-        let config = config.clone();
+                    if !matches!(stop_rx.try_recv(), Err(TryRecvError::Empty)) {
+                        break 'outer;
+                    }
 
-        println!(
-            "Spawning thread for {} (at {})",
-            controller.name(),
-            controller.location()
-        );
+                    let mut events = Vec::new();
 
-        std::thread::spawn(move || {
-            if smol::block_on(async {
-                controller.init().await?;
-                controller.turn_off_leds().await?;
-                Ok::<(), OpenRgbError>(())
-            })
-            .is_err()
-            {
-                tx.send(()).unwrap();
-                return;
-            };
-
-            let mut func = FadeLeds::new(&controller);
-            'outer: loop {
-                std::thread::sleep(Duration::from_millis(sleep_time));
-                let mut events = Vec::new();
-
-                loop {
-                    match hid_event_reader.try_recv() {
-                        Err(TryRecvError::Empty) => break,
-                        Ok(event) => {
-                            events.push(event);
+                    loop {
+                        match hid_event_reader.try_recv() {
+                            Err(TryRecvError::Empty) => break,
+                            Ok(event) => {
+                                events.push(event);
+                            }
+                            Err(TryRecvError::Disconnected) => break 'outer,
                         }
-                        Err(TryRecvError::Disconnected) => break 'outer,
                     }
-                }
 
-                if func.update(&config, &events, &keymap, &controller).is_err() {
-                    break;
+                    if func.update(&config, &events, &keymap, &controller).is_err() {
+                        break;
+                    }
                 }
-            }
 
-            tx.send(()).unwrap();
-        });
+                tx.send(()).unwrap();
+            });
 
-        Some((location, thread_exited))
-    };
+            Some((location, thread_exited, stop_tx))
+        };
 
-    let mut active_devices: HashMap<String, Receiver<()>> = HashMap::new();
+    let mut active_devices: HashMap<String, (Receiver<()>, mpsc::Sender<()>)> = HashMap::new();
+    let device_changes = device_watch::watch();
 
     loop {
         let to_remove = active_devices
             .iter()
-            .filter_map(|(path, recv)| match recv.try_recv() {
+            .filter_map(|(path, (recv, _))| match recv.try_recv() {
                 Ok(_) | Err(TryRecvError::Disconnected) => {
                     println!("Thread for {path} closed. Removing from active.");
 
@@ -239,6 +255,31 @@ async fn main() -> OpenRgbResult<()> {
             active_devices.remove(&path);
         }
 
+        // Re-scan OpenRGB's controllers whenever a hidraw/event node appears, and proactively
+        // drop + stop the worker for one that disappears, instead of busy-polling.
+        match device_changes.recv_timeout(Duration::from_secs(5)) {
+            Ok(DeviceChange::Removed(node_path)) => {
+                if let Some(key) = active_devices
+                    .keys()
+                    .find(|location| device_watch::location_matches(location, &node_path))
+                    .cloned()
+                {
+                    println!("{node_path} unplugged. Stopping its worker thread.");
+                    if let Some((_, stop_tx)) = active_devices.remove(&key) {
+                        let _ = stop_tx.send(());
+                    }
+                }
+
+                continue;
+            }
+            Ok(DeviceChange::Added(_)) => {}
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                println!("Hotplug watcher channel closed; falling back to periodic scanning.");
+                Timer::after(Duration::from_secs(5)).await;
+            }
+        }
+
         let controllers = client
             .get_controllers_of_type(DeviceType::Keyboard)
             .await
@@ -251,11 +292,9 @@ async fn main() -> OpenRgbResult<()> {
         // this means that we can't get updated device locations if a device is un- and then replugged while the server is running.
         for mut controller in controllers {
             controller.sync_controller_data().await.unwrap();
-            if let Some((key, value)) = try_setup_thread(controller) {
-                active_devices.insert(key, value);
+            if let Some((key, recv, stop_tx)) = try_setup_thread(controller) {
+                active_devices.insert(key, (recv, stop_tx));
             }
         }
-
-        Timer::after(Duration::from_millis(100)).await;
     }
 }
@@ -0,0 +1,36 @@
+use openrgb2::{Controller, OpenRgbResult};
+
+use crate::{
+    LedFunction,
+    config::{Config, Options},
+    input::KeyEvent,
+    key_mappings::KeyMapping,
+};
+
+/// Ignores key events and keeps every LED at `config.color()`. The simplest possible
+/// registry entry, for controllers that should stay put regardless of what's typed.
+pub struct StaticColor;
+
+impl LedFunction for StaticColor {
+    fn new(_controller: &Controller, _opts: &Options) -> Self {
+        Self
+    }
+
+    fn update(
+        &mut self,
+        config: &Config,
+        _events: &[KeyEvent],
+        _key_map: &KeyMapping,
+        controller: &Controller,
+    ) -> OpenRgbResult<()> {
+        let mut cmd = controller.cmd();
+
+        for led in controller.led_iter() {
+            cmd.set_led(led.id(), config.color())?;
+        }
+
+        futures_lite::future::block_on(cmd.execute())?;
+
+        Ok(())
+    }
+}